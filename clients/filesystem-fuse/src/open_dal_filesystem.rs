@@ -22,23 +22,72 @@ use crate::filesystem::{
 };
 use crate::opened_file::{OpenFileFlags, OpenedFile};
 use async_trait::async_trait;
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use fuse3::FileType::{Directory, RegularFile};
 use fuse3::{Errno, FileType, Timestamp};
 use log::error;
 use opendal::{EntryMode, ErrorKind, Metadata, Operator};
+use std::collections::{BTreeMap, HashMap};
+use std::io::SeekFrom;
 use std::path::{Path, PathBuf};
-use std::time::SystemTime;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime};
+use tokio::io::{AsyncReadExt, AsyncSeekExt, AsyncWriteExt};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Identifies one `FileWriterImpl`'s entry in `active_writers` independent
+/// of its path, so two concurrent opens-for-write on the same path don't
+/// clobber each other's bookkeeping: the second open adds its own entry
+/// next to the first's instead of replacing it, and either writer's
+/// `close` only ever removes its own entry.
+static WRITER_ID_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn next_writer_id() -> u64 {
+    WRITER_ID_COUNTER.fetch_add(1, Ordering::Relaxed)
+}
+
+type ActiveWriters = Arc<AsyncMutex<HashMap<PathBuf, Vec<(u64, Arc<AsyncMutex<WriteStaging>>)>>>>;
 
 pub(crate) struct OpenDalFileSystem {
     op: Operator,
+    meta_cache: Arc<MetaCache>,
+    readahead_window_bytes: u64,
+    // Staged writers currently open for each path, so `set_attr` can
+    // resize an in-flight write's target length instead of racing it.
+    // Multiple entries per path are possible when the same file is open
+    // for write through more than one fd at once.
+    active_writers: ActiveWriters,
+    write_staging_cache_dir: PathBuf,
 }
 
 impl OpenDalFileSystem {}
 
 impl OpenDalFileSystem {
-    pub(crate) fn new(op: Operator, _config: &AppConfig, _fs_context: &FileSystemContext) -> Self {
-        Self { op: op }
+    pub(crate) fn new(op: Operator, config: &AppConfig, _fs_context: &FileSystemContext) -> Self {
+        let meta_cache = Arc::new(MetaCache::new(
+            config.meta_cache_ttl(),
+            config.meta_cache_capacity(),
+        ));
+        Self {
+            op,
+            meta_cache,
+            readahead_window_bytes: config.readahead_window_bytes(),
+            active_writers: Arc::new(AsyncMutex::new(HashMap::new())),
+            write_staging_cache_dir: config.write_staging_cache_dir(),
+        }
+    }
+
+    /// Builds the backend `Operator` for whichever storage URI scheme
+    /// `config` selects (`s3://`, `gs://`, `abfss://`, `fs://`,
+    /// `memory://`) via the operator registry, then wraps it the same as
+    /// `new`. Mount entry points should call this instead of hand-rolling
+    /// an `Operator` so switching backends is a config change, not a code
+    /// change; `new` stays around for callers that already have an
+    /// `Operator` on hand (e.g. tests against `services::Memory`).
+    pub(crate) fn from_config(config: &AppConfig, fs_context: &FileSystemContext) -> Result<Self> {
+        let op = crate::operator_registry::build_operator(config)?;
+        Ok(Self::new(op, config, fs_context))
     }
 
     fn opendal_meta_to_file_stat(&self, meta: &Metadata, file_stat: &mut FileStat) {
@@ -60,6 +109,10 @@ impl PathFileSystem for OpenDalFileSystem {
     }
 
     async fn stat(&self, path: &Path) -> Result<FileStat> {
+        if let Some(cached) = self.meta_cache.get_stat(path).await {
+            return cached;
+        }
+
         let file_name = path.to_string_lossy().to_string();
         let meta_result = self.op.stat(&file_name).await;
 
@@ -69,10 +122,17 @@ impl PathFileSystem for OpenDalFileSystem {
             Err(err) => {
                 if err.kind() == ErrorKind::NotFound {
                     let dir_name = build_dir_path(path);
-                    self.op
-                        .stat(&dir_name)
-                        .await
-                        .map_err(opendal_error_to_errno)?
+                    match self.op.stat(&dir_name).await {
+                        Ok(meta) => meta,
+                        Err(err) => {
+                            let not_found = err.kind() == ErrorKind::NotFound;
+                            let errno = opendal_error_to_errno(err);
+                            if not_found {
+                                self.meta_cache.put_not_found(path).await;
+                            }
+                            return Err(errno);
+                        }
+                    }
                 } else {
                     return Err(opendal_error_to_errno(err));
                 }
@@ -82,10 +142,15 @@ impl PathFileSystem for OpenDalFileSystem {
         let mut file_stat = FileStat::new_file_filestat_with_path(path, 0);
         self.opendal_meta_to_file_stat(&meta, &mut file_stat);
 
+        self.meta_cache.put_stat(path, file_stat.clone()).await;
         Ok(file_stat)
     }
 
     async fn read_dir(&self, path: &Path) -> Result<Vec<FileStat>> {
+        if let Some(cached) = self.meta_cache.get_dir(path).await {
+            return Ok(cached);
+        }
+
         // dir name should end with '/' in opendal.
         let dir_name = build_dir_path(path);
         let entries = self
@@ -93,7 +158,7 @@ impl PathFileSystem for OpenDalFileSystem {
             .list(&dir_name)
             .await
             .map_err(opendal_error_to_errno)?;
-        entries
+        let entries: Result<Vec<FileStat>> = entries
             .iter()
             .map(|entry| {
                 let mut path = PathBuf::from(path);
@@ -103,30 +168,52 @@ impl PathFileSystem for OpenDalFileSystem {
                 self.opendal_meta_to_file_stat(entry.metadata(), &mut file_stat);
                 Ok(file_stat)
             })
-            .collect()
+            .collect();
+        let entries = entries?;
+
+        self.meta_cache.put_dir(path, entries.clone()).await;
+        Ok(entries)
     }
 
     async fn open_file(&self, path: &Path, flags: OpenFileFlags) -> Result<OpenedFile> {
         let file_stat = self.stat(path).await?;
         debug_assert!(file_stat.kind == RegularFile);
+        let original_len = file_stat.size;
 
         let mut file = OpenedFile::new(file_stat);
         let file_name = path.to_string_lossy().to_string();
         if flags.is_read() {
-            let reader = self
-                .op
-                .reader_with(&file_name)
-                .await
-                .map_err(opendal_error_to_errno)?;
-            file.reader = Some(Box::new(FileReaderImpl { reader }));
+            file.reader = Some(Box::new(FileReaderImpl {
+                op: self.op.clone(),
+                file_name: file_name.clone(),
+                last_served_end: 0,
+                window: Arc::new(AsyncMutex::new(None)),
+                max_window_bytes: self.readahead_window_bytes,
+                pending_prefetch: None,
+            }));
         }
         if flags.is_write() || flags.is_create() || flags.is_append() || flags.is_truncate() {
-            let writer = self
-                .op
-                .writer_with(&file_name)
+            let original_len = if flags.is_truncate() { 0 } else { original_len };
+            let staging = Arc::new(AsyncMutex::new(WriteStaging::new(
+                original_len,
+                self.write_staging_cache_dir.clone(),
+            )));
+            let writer_id = next_writer_id();
+            self.active_writers
+                .lock()
                 .await
-                .map_err(opendal_error_to_errno)?;
-            file.writer = Some(Box::new(FileWriterImpl { writer }));
+                .entry(path.to_path_buf())
+                .or_default()
+                .push((writer_id, staging.clone()));
+            file.writer = Some(Box::new(FileWriterImpl {
+                op: self.op.clone(),
+                file_name,
+                staging,
+                meta_cache: self.meta_cache.clone(),
+                active_writers: self.active_writers.clone(),
+                path: path.to_path_buf(),
+                writer_id,
+            }));
         }
         Ok(file)
     }
@@ -149,6 +236,7 @@ impl PathFileSystem for OpenDalFileSystem {
             .map_err(opendal_error_to_errno)?;
 
         writer.close().await.map_err(opendal_error_to_errno)?;
+        self.meta_cache.invalidate(path).await;
 
         let file = self.open_file(path, flags).await?;
         Ok(file)
@@ -160,12 +248,61 @@ impl PathFileSystem for OpenDalFileSystem {
             .create_dir(&dir_name)
             .await
             .map_err(opendal_error_to_errno)?;
+        self.meta_cache.invalidate(path).await;
         let file_stat = self.stat(path).await?;
         Ok(file_stat)
     }
 
-    async fn set_attr(&self, _path: &Path, _file_stat: &FileStat, _flush: bool) -> Result<()> {
-        // no need to implement
+    async fn set_attr(&self, path: &Path, file_stat: &FileStat, _flush: bool) -> Result<()> {
+        let new_len = file_stat.size;
+
+        let active_writers = self.active_writers.lock().await.get(path).cloned();
+        if let Some(writers) = active_writers {
+            if !writers.is_empty() {
+                // One or more writers are already open on this path: resize
+                // every one of their staged target lengths directly so the
+                // truncate survives whatever they later flush on close(),
+                // instead of being clobbered by it.
+                for (_, staging) in &writers {
+                    staging.lock().await.set_target_len(new_len);
+                }
+                self.meta_cache.invalidate(path).await;
+                return Ok(());
+            }
+        }
+
+        let file_name = path.to_string_lossy().to_string();
+        let current = self.op.stat(&file_name).await.map_err(opendal_error_to_errno)?;
+
+        if new_len == current.content_length() {
+            return Ok(());
+        }
+
+        let content = if new_len < current.content_length() {
+            // Shrink: keep only the first `new_len` bytes of the object.
+            self.op
+                .read_with(&file_name)
+                .range(0..new_len)
+                .await
+                .map_err(opendal_error_to_errno)?
+                .to_vec()
+        } else {
+            // Grow: zero-fill past the current end.
+            let mut content = self
+                .op
+                .read(&file_name)
+                .await
+                .map_err(opendal_error_to_errno)?
+                .to_vec();
+            content.resize(new_len as usize, 0);
+            content
+        };
+
+        self.op
+            .write(&file_name, content)
+            .await
+            .map_err(opendal_error_to_errno)?;
+        self.meta_cache.invalidate(path).await;
         Ok(())
     }
 
@@ -174,7 +311,9 @@ impl PathFileSystem for OpenDalFileSystem {
         self.op
             .remove(vec![file_name])
             .await
-            .map_err(opendal_error_to_errno)
+            .map_err(opendal_error_to_errno)?;
+        self.meta_cache.invalidate(path).await;
+        Ok(())
     }
 
     async fn remove_dir(&self, path: &Path) -> Result<()> {
@@ -183,7 +322,45 @@ impl PathFileSystem for OpenDalFileSystem {
         self.op
             .remove(vec![dir_name])
             .await
-            .map_err(opendal_error_to_errno)
+            .map_err(opendal_error_to_errno)?;
+        self.meta_cache.invalidate(path).await;
+        Ok(())
+    }
+
+    async fn rename(&self, from: &Path, to: &Path) -> Result<()> {
+        let from_stat = self.stat(from).await?;
+        let to_stat = self.stat(to).await;
+
+        match &to_stat {
+            Ok(existing) if from_stat.kind == RegularFile && existing.kind == Directory => {
+                return Err(Errno::from(libc::EISDIR));
+            }
+            Ok(existing) if from_stat.kind == Directory && existing.kind == RegularFile => {
+                return Err(Errno::from(libc::ENOTDIR));
+            }
+            Ok(existing) if existing.kind == Directory => {
+                if !self.read_dir(to).await?.is_empty() {
+                    return Err(Errno::from(libc::ENOTEMPTY));
+                }
+            }
+            Err(err) if *err != Errno::from(libc::ENOENT) => return Err(*err),
+            _ => {}
+        }
+
+        if from_stat.kind == Directory {
+            self.rename_dir(from, to).await?;
+            // A directory rename moves every nested entry too, so a cached
+            // `stat`/`read_dir` for anything under the old prefix would
+            // otherwise keep returning stale `Found` results until TTL
+            // expiry instead of the `ENOENT` they should get now.
+            self.meta_cache.invalidate_subtree(from).await;
+            self.meta_cache.invalidate_subtree(to).await;
+        } else {
+            self.rename_file(from, to).await?;
+            self.meta_cache.invalidate(from).await;
+            self.meta_cache.invalidate(to).await;
+        }
+        Ok(())
     }
 
     fn get_capacity(&self) -> Result<FileSystemCapacity> {
@@ -191,43 +368,674 @@ impl PathFileSystem for OpenDalFileSystem {
     }
 }
 
+impl OpenDalFileSystem {
+    async fn rename_file(&self, from: &Path, to: &Path) -> Result<()> {
+        let from_name = from.to_string_lossy().to_string();
+        let to_name = to.to_string_lossy().to_string();
+
+        if self.op.info().full_capability().rename {
+            return self
+                .op
+                .rename(&from_name, &to_name)
+                .await
+                .map_err(opendal_error_to_errno);
+        }
+
+        self.op
+            .copy(&from_name, &to_name)
+            .await
+            .map_err(opendal_error_to_errno)?;
+        self.op
+            .remove(vec![from_name])
+            .await
+            .map_err(opendal_error_to_errno)
+    }
+
+    async fn rename_dir(&self, from: &Path, to: &Path) -> Result<()> {
+        let from_dir = build_dir_path(from);
+        let to_dir = build_dir_path(to);
+
+        if self.op.info().full_capability().rename {
+            return self
+                .op
+                .rename(&from_dir, &to_dir)
+                .await
+                .map_err(opendal_error_to_errno);
+        }
+
+        let entries = self
+            .op
+            .list_with(&from_dir)
+            .recursive(true)
+            .await
+            .map_err(opendal_error_to_errno)?;
+
+        // Recreate the destination directory marker itself first. Without
+        // this, an empty source directory (or one whose only children are
+        // themselves empty subdirectories) copies nothing and `mv` ends up
+        // just deleting the source instead of renaming it.
+        self.op
+            .create_dir(&to_dir)
+            .await
+            .map_err(opendal_error_to_errno)?;
+
+        let mut to_remove = Vec::with_capacity(entries.len() + 1);
+        for entry in &entries {
+            let rel = entry.path().strip_prefix(&from_dir).unwrap_or(entry.path());
+            let dst = format!("{to_dir}{rel}");
+            if entry.metadata().mode() == EntryMode::DIR {
+                self.op
+                    .create_dir(&dst)
+                    .await
+                    .map_err(opendal_error_to_errno)?;
+            } else {
+                self.op
+                    .copy(entry.path(), &dst)
+                    .await
+                    .map_err(opendal_error_to_errno)?;
+            }
+            to_remove.push(entry.path().to_string());
+        }
+        to_remove.push(from_dir);
+
+        self.op
+            .remove(to_remove)
+            .await
+            .map_err(opendal_error_to_errno)
+    }
+}
+
+/// Number of bytes fetched per concurrent range request while filling a
+/// readahead window.
+const READAHEAD_CHUNK_BYTES: u64 = 2 * 1024 * 1024;
+
+/// A contiguous run of bytes prefetched ahead of the offset a caller last
+/// asked for, used to serve sequential reads without waiting on a fresh
+/// round trip to the backend for every request.
+struct PrefetchWindow {
+    start: u64,
+    data: Bytes,
+}
+
+impl PrefetchWindow {
+    fn end(&self) -> u64 {
+        self.start + self.data.len() as u64
+    }
+
+    fn covers(&self, offset: u64, end: u64) -> bool {
+        offset >= self.start && end <= self.end()
+    }
+
+    fn slice(&self, offset: u64, end: u64) -> Bytes {
+        let start = (offset - self.start) as usize;
+        let end = (end - self.start) as usize;
+        self.data.slice(start..end)
+    }
+}
+
 struct FileReaderImpl {
-    reader: opendal::Reader,
+    op: Operator,
+    file_name: String,
+    last_served_end: u64,
+    // Shared with the background prefetch task spawned below, so it can
+    // stash its result without the triggering `read()` call waiting on it.
+    window: Arc<AsyncMutex<Option<PrefetchWindow>>>,
+    max_window_bytes: u64,
+    // The readahead task kicked off by the most recent sequential read, if
+    // it hasn't been reaped yet. Never awaited from `read()`: a later read
+    // that detects a seek aborts it before resetting `window`.
+    pending_prefetch: Option<tokio::task::JoinHandle<()>>,
+}
+
+impl FileReaderImpl {
+    async fn read_range(&self, offset: u64, end: u64) -> Result<Bytes> {
+        let v = self
+            .op
+            .read_with(&self.file_name)
+            .range(offset..end)
+            .await
+            .map_err(opendal_error_to_errno)?;
+        Ok(v.to_bytes())
+    }
+
+    /// Kicks off a background task that fetches `max_window_bytes` (never
+    /// less than one `READAHEAD_CHUNK_BYTES` chunk) starting at `offset` as
+    /// several concurrent range reads and stashes the result
+    /// as the new prefetch window, without making `read()` wait on it. A
+    /// partial or failed chunk just shrinks the window instead of failing
+    /// anything, since the read that triggered this has already been served
+    /// directly.
+    ///
+    /// No-ops if a previous prefetch is still in flight -- on a sequential
+    /// scan that outruns a single window-sized fetch, every read would
+    /// otherwise spawn its own full-window fetch racing the ones before it,
+    /// multiplying backend calls instead of cutting them.
+    fn spawn_prefetch(&mut self, offset: u64) {
+        if let Some(handle) = &self.pending_prefetch {
+            if !handle.is_finished() {
+                return;
+            }
+        }
+
+        let window_end = offset + self.max_window_bytes.max(READAHEAD_CHUNK_BYTES);
+        let op = self.op.clone();
+        let file_name = self.file_name.clone();
+        let window = self.window.clone();
+
+        self.pending_prefetch = Some(tokio::spawn(async move {
+            let mut tasks = Vec::new();
+            let mut start = offset;
+            while start < window_end {
+                let chunk_end = (start + READAHEAD_CHUNK_BYTES).min(window_end);
+                let op = op.clone();
+                let file_name = file_name.clone();
+                tasks.push(tokio::spawn(async move {
+                    op.read_with(&file_name).range(start..chunk_end).await
+                }));
+                start = chunk_end;
+            }
+
+            let mut buffer = BytesMut::with_capacity((window_end - offset) as usize);
+            for task in tasks {
+                match task.await {
+                    Ok(Ok(chunk)) => buffer.extend_from_slice(&chunk.to_bytes()),
+                    _ => break,
+                }
+            }
+
+            if !buffer.is_empty() {
+                *window.lock().await = Some(PrefetchWindow {
+                    start: offset,
+                    data: buffer.freeze(),
+                });
+            }
+        }));
+    }
+
+    /// Cancels a previously kicked-off prefetch instead of waiting for it,
+    /// since whatever window it would have produced is about to be thrown
+    /// away. Called right before the window is reset on a detected random
+    /// seek, so that read isn't penalized by however long a stale,
+    /// now-known-wrong multi-MB sequential readahead guess takes to finish.
+    fn cancel_pending_prefetch(&mut self) {
+        if let Some(handle) = self.pending_prefetch.take() {
+            handle.abort();
+        }
+    }
 }
 
 #[async_trait]
 impl FileReader for FileReaderImpl {
     async fn read(&mut self, offset: u64, size: u32) -> Result<Bytes> {
         let end = offset + size as u64;
-        let v = self
-            .reader
-            .read(offset..end)
+
+        if let Some(window) = self.window.lock().await.as_ref() {
+            if window.covers(offset, end) {
+                let data = window.slice(offset, end);
+                self.last_served_end = end;
+                return Ok(data);
+            }
+        }
+
+        let sequential = offset == self.last_served_end;
+        self.last_served_end = end;
+
+        if !sequential {
+            // A seek away from the last served offset invalidates any
+            // in-flight readahead assumption.
+            self.cancel_pending_prefetch();
+            *self.window.lock().await = None;
+            return self.read_range(offset, end).await;
+        }
+
+        // Serve this request with a direct range read so the caller never
+        // blocks on readahead, then kick off a background task that fills
+        // the window for the *next* sequential read while this one already
+        // has its answer.
+        let data = self.read_range(offset, end).await?;
+        self.spawn_prefetch(end);
+        Ok(data)
+    }
+}
+
+/// Tracks which byte ranges of a [`WriteStaging`] buffer have actually been
+/// written, so `close` can tell committed data apart from unwritten gaps
+/// that need to be backfilled from the original object before upload.
+#[derive(Default)]
+struct DirtyRanges {
+    // start -> end, kept merged and non-overlapping.
+    ranges: BTreeMap<u64, u64>,
+}
+
+impl DirtyRanges {
+    fn mark(&mut self, start: u64, end: u64) {
+        if start >= end {
+            return;
+        }
+        let mut new_start = start;
+        let mut new_end = end;
+
+        // Merge with any existing range that overlaps or touches [start, end).
+        let overlapping: Vec<(u64, u64)> = self
+            .ranges
+            .range(..=end)
+            .filter(|(_, &r_end)| r_end >= start)
+            .map(|(&s, &e)| (s, e))
+            .collect();
+        for (s, e) in overlapping {
+            new_start = new_start.min(s);
+            new_end = new_end.max(e);
+            self.ranges.remove(&s);
+        }
+        self.ranges.insert(new_start, new_end);
+    }
+
+    /// Returns the unwritten gaps within `[0, len)`.
+    fn gaps(&self, len: u64) -> Vec<(u64, u64)> {
+        let mut gaps = Vec::new();
+        let mut cursor = 0u64;
+        for (&start, &end) in &self.ranges {
+            if start > cursor {
+                gaps.push((cursor, start.min(len)));
+            }
+            cursor = cursor.max(end);
+        }
+        if cursor < len {
+            gaps.push((cursor, len));
+        }
+        gaps.retain(|(s, e)| s < e);
+        gaps
+    }
+
+    fn is_fully_covered(&self, len: u64) -> bool {
+        self.gaps(len).is_empty()
+    }
+
+    /// Drops (or trims) any range beyond `len`, used when a truncate
+    /// shrinks the staged length after some ranges were already marked.
+    fn clip(&mut self, len: u64) {
+        let clipped: BTreeMap<u64, u64> = self
+            .ranges
+            .iter()
+            .filter(|(&start, _)| start < len)
+            .map(|(&start, &end)| (start, end.min(len)))
+            .collect();
+        self.ranges = clipped;
+    }
+}
+
+/// Above this staged size, writes spill from an in-memory buffer to a temp
+/// file under the configured write-staging cache dir, so a large
+/// sequential write through the mount doesn't have to hold the whole
+/// object in RAM.
+const STAGING_MEMORY_LIMIT_BYTES: u64 = 8 * 1024 * 1024;
+/// Chunk size used when streaming a spilled (file-backed) scratch to the
+/// backend on close.
+const STAGING_UPLOAD_CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+static STAGING_FILE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+fn new_staging_path(cache_dir: &Path) -> PathBuf {
+    let id = STAGING_FILE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    cache_dir.join(format!("gravitino-fuse-staging-{}-{id}.tmp", std::process::id()))
+}
+
+fn staging_io_error_to_errno(err: std::io::Error) -> Errno {
+    error!("write staging scratch IO error {:?}", err);
+    Errno::from(libc::EIO)
+}
+
+/// Where a [`WriteStaging`]'s scratch data actually lives. Small staged
+/// writes stay in an in-memory buffer; once they cross
+/// `STAGING_MEMORY_LIMIT_BYTES` the buffer is spilled to a temp file so
+/// memory use stays bounded regardless of how large the write is.
+enum Scratch {
+    Memory(Vec<u8>),
+    File { file: tokio::fs::File, path: PathBuf },
+}
+
+impl Scratch {
+    async fn spill(existing: Vec<u8>, cache_dir: &Path) -> std::io::Result<Scratch> {
+        tokio::fs::create_dir_all(cache_dir).await.ok();
+        let path = new_staging_path(cache_dir);
+        let mut file = tokio::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&path)
+            .await?;
+        file.write_all(&existing).await?;
+        Ok(Scratch::File { file, path })
+    }
+
+    async fn write_at(&mut self, offset: u64, data: &[u8], cache_dir: &Path) -> std::io::Result<()> {
+        if let Scratch::Memory(buf) = self {
+            let end = offset + data.len() as u64;
+            if end > STAGING_MEMORY_LIMIT_BYTES {
+                *self = Scratch::spill(std::mem::take(buf), cache_dir).await?;
+            }
+        }
+        match self {
+            Scratch::Memory(buf) => {
+                let end = offset as usize + data.len();
+                if end > buf.len() {
+                    buf.resize(end, 0);
+                }
+                buf[offset as usize..end].copy_from_slice(data);
+            }
+            Scratch::File { file, .. } => {
+                file.seek(SeekFrom::Start(offset)).await?;
+                file.write_all(data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Copies backfilled bytes (read from the original object) into
+    /// `[start, end)` of the scratch.
+    async fn write_range(&mut self, start: u64, end: u64, data: &[u8]) -> std::io::Result<()> {
+        match self {
+            Scratch::Memory(buf) => buf[start as usize..end as usize].copy_from_slice(data),
+            Scratch::File { file, .. } => {
+                file.seek(SeekFrom::Start(start)).await?;
+                file.write_all(data).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Grows or shrinks the scratch to exactly `len`, zero-filling any
+    /// newly grown region.
+    async fn resize(&mut self, len: u64, cache_dir: &Path) -> std::io::Result<()> {
+        if let Scratch::Memory(buf) = self {
+            if len > STAGING_MEMORY_LIMIT_BYTES {
+                *self = Scratch::spill(std::mem::take(buf), cache_dir).await?;
+            }
+        }
+        match self {
+            Scratch::Memory(buf) => buf.resize(len as usize, 0),
+            Scratch::File { file, .. } => file.set_len(len).await?,
+        }
+        Ok(())
+    }
+
+    /// Streams the scratch content to `writer` in fixed-size chunks so a
+    /// file-backed scratch never has to be fully materialized in memory;
+    /// backends that support multipart uploads assemble these sequential
+    /// writes into parts internally via opendal's `Writer`.
+    async fn upload(&mut self, writer: &mut opendal::Writer) -> Result<()> {
+        match self {
+            Scratch::Memory(buf) => writer.write(buf.clone()).await.map_err(opendal_error_to_errno),
+            Scratch::File { file, .. } => {
+                file.seek(SeekFrom::Start(0))
+                    .await
+                    .map_err(staging_io_error_to_errno)?;
+                let mut chunk = vec![0u8; STAGING_UPLOAD_CHUNK_BYTES];
+                loop {
+                    let n = file
+                        .read(&mut chunk)
+                        .await
+                        .map_err(staging_io_error_to_errno)?;
+                    if n == 0 {
+                        break;
+                    }
+                    writer
+                        .write(chunk[..n].to_vec())
+                        .await
+                        .map_err(opendal_error_to_errno)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl Drop for Scratch {
+    fn drop(&mut self) {
+        if let Scratch::File { path, .. } = self {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+}
+
+/// Per-open-file scratch area that makes `FileWriterImpl` tolerant of
+/// random-offset writes even though `opendal::Writer` only supports
+/// sequential appends. Writes land in [`Scratch`] at the right offset;
+/// `close` reconciles it with the original object and streams the final
+/// content to the backend.
+///
+/// `target_len` is the authoritative final object length: it starts out
+/// seeded from the pre-existing object size (0 for a brand new file or an
+/// `O_TRUNC` open) and is the length `close()` materializes, independent
+/// of how far any individual `write()` happened to reach. Without this, a
+/// write that only touches a prefix of an existing file (e.g. rewriting a
+/// header) would make `close()` treat the write's own length as the whole
+/// file and silently drop everything past it.
+struct WriteStaging {
+    scratch: Scratch,
+    dirty: DirtyRanges,
+    target_len: u64,
+    cache_dir: PathBuf,
+}
+
+impl WriteStaging {
+    fn new(original_len: u64, cache_dir: PathBuf) -> Self {
+        Self {
+            scratch: Scratch::Memory(Vec::new()),
+            dirty: DirtyRanges::default(),
+            target_len: original_len,
+            cache_dir,
+        }
+    }
+
+    async fn write_at(&mut self, offset: u64, data: &[u8]) -> Result<()> {
+        if data.is_empty() {
+            return Ok(());
+        }
+        let end = offset + data.len() as u64;
+        self.scratch
+            .write_at(offset, data, &self.cache_dir)
             .await
-            .map_err(opendal_error_to_errno)?;
-        Ok(v.to_bytes())
+            .map_err(staging_io_error_to_errno)?;
+        self.dirty.mark(offset, end);
+        self.target_len = self.target_len.max(end);
+        Ok(())
+    }
+
+    /// `ftruncate`/`set_attr`-driven resize to exactly `new_len`,
+    /// independent of what has been written so far. The scratch itself is
+    /// only physically resized in `close()`, so a rapid sequence of
+    /// truncates doesn't do extra IO.
+    fn set_target_len(&mut self, new_len: u64) {
+        self.target_len = new_len;
+        self.dirty.clip(new_len);
+    }
+
+    fn final_len(&self) -> u64 {
+        self.target_len
     }
 }
 
 struct FileWriterImpl {
-    writer: opendal::Writer,
+    op: Operator,
+    file_name: String,
+    // Shared with `OpenDalFileSystem::active_writers` for the lifetime of
+    // this open file, so a concurrent `set_attr` on the same path can
+    // resize the staged target length instead of racing this writer.
+    staging: Arc<AsyncMutex<WriteStaging>>,
+    meta_cache: Arc<MetaCache>,
+    active_writers: ActiveWriters,
+    path: PathBuf,
+    // This writer's own entry in `active_writers`, so `close` only ever
+    // removes itself even if another fd is concurrently writing the same
+    // path.
+    writer_id: u64,
 }
 
 #[async_trait]
 impl FileWriter for FileWriterImpl {
-    async fn write(&mut self, _offset: u64, data: &[u8]) -> Result<u32> {
-        self.writer
-            .write(data.to_vec())
-            .await
-            .map_err(opendal_error_to_errno)?;
+    async fn write(&mut self, offset: u64, data: &[u8]) -> Result<u32> {
+        self.staging.lock().await.write_at(offset, data).await?;
         Ok(data.len() as u32)
     }
 
     async fn close(&mut self) -> Result<()> {
-        self.writer.close().await.map_err(opendal_error_to_errno)?;
+        let mut staging = self.staging.lock().await;
+        let len = staging.final_len();
+        let cache_dir = staging.cache_dir.clone();
+        staging
+            .scratch
+            .resize(len, &cache_dir)
+            .await
+            .map_err(staging_io_error_to_errno)?;
+        if !staging.dirty.is_fully_covered(len) {
+            // Some of the staged range was never written (e.g. a
+            // read-modify-write that only touched part of the file), so
+            // pull the original bytes in to fill the gaps.
+            if let Ok(original) = self.op.read(&self.file_name).await {
+                let original = original.to_bytes();
+                for (start, end) in staging.dirty.gaps(len) {
+                    let fill_end = (end as usize).min(original.len()) as u64;
+                    if start < fill_end {
+                        staging
+                            .scratch
+                            .write_range(start, fill_end, &original[start as usize..fill_end as usize])
+                            .await
+                            .map_err(staging_io_error_to_errno)?;
+                    }
+                }
+            }
+        }
+
+        let mut writer = self
+            .op
+            .writer_with(&self.file_name)
+            .await
+            .map_err(opendal_error_to_errno)?;
+        staging.scratch.upload(&mut writer).await?;
+        writer.close().await.map_err(opendal_error_to_errno)?;
+        drop(staging);
+
+        self.meta_cache.invalidate(&self.path).await;
+        let mut active_writers = self.active_writers.lock().await;
+        if let Some(writers) = active_writers.get_mut(&self.path) {
+            writers.retain(|(id, _)| *id != self.writer_id);
+            if writers.is_empty() {
+                active_writers.remove(&self.path);
+            }
+        }
         Ok(())
     }
 }
 
+/// A bounded cache of `stat`/`read_dir` results keyed by path, including
+/// negative (not-found) lookups, so repeated FUSE metadata requests for the
+/// same path don't each round-trip to the object store. Entries expire
+/// after `ttl` and are invalidated explicitly by mutating operations.
+/// When a map reaches `capacity`, expired entries are dropped first, then
+/// the entry inserted longest ago; lookups do not refresh an entry's
+/// position, so eviction follows insertion order, not recency of use.
+struct MetaCache {
+    stats: AsyncMutex<HashMap<PathBuf, (CachedStat, Instant)>>,
+    dirs: AsyncMutex<HashMap<PathBuf, (Vec<FileStat>, Instant)>>,
+    ttl: Duration,
+    capacity: usize,
+}
+
+#[derive(Clone)]
+enum CachedStat {
+    Found(FileStat),
+    NotFound,
+}
+
+impl MetaCache {
+    fn new(ttl: Duration, capacity: usize) -> Self {
+        Self {
+            stats: AsyncMutex::new(HashMap::new()),
+            dirs: AsyncMutex::new(HashMap::new()),
+            ttl,
+            capacity,
+        }
+    }
+
+    async fn get_stat(&self, path: &Path) -> Option<Result<FileStat>> {
+        let stats = self.stats.lock().await;
+        let (cached, inserted_at) = stats.get(path)?;
+        if inserted_at.elapsed() > self.ttl {
+            return None;
+        }
+        match cached {
+            CachedStat::Found(stat) => Some(Ok(stat.clone())),
+            CachedStat::NotFound => Some(Err(Errno::from(libc::ENOENT))),
+        }
+    }
+
+    async fn get_dir(&self, path: &Path) -> Option<Vec<FileStat>> {
+        let dirs = self.dirs.lock().await;
+        let (entries, inserted_at) = dirs.get(path)?;
+        if inserted_at.elapsed() > self.ttl {
+            return None;
+        }
+        Some(entries.clone())
+    }
+
+    async fn put_stat(&self, path: &Path, stat: FileStat) {
+        let mut stats = self.stats.lock().await;
+        evict_expired_if_full(&mut stats, self.ttl, self.capacity);
+        stats.insert(path.to_path_buf(), (CachedStat::Found(stat), Instant::now()));
+    }
+
+    async fn put_not_found(&self, path: &Path) {
+        let mut stats = self.stats.lock().await;
+        evict_expired_if_full(&mut stats, self.ttl, self.capacity);
+        stats.insert(path.to_path_buf(), (CachedStat::NotFound, Instant::now()));
+    }
+
+    async fn put_dir(&self, path: &Path, entries: Vec<FileStat>) {
+        let mut dirs = self.dirs.lock().await;
+        evict_expired_if_full(&mut dirs, self.ttl, self.capacity);
+        dirs.insert(path.to_path_buf(), (entries, Instant::now()));
+    }
+
+    /// Drops `path` and its parent directory's listing from the cache,
+    /// called after any operation that mutates the namespace.
+    async fn invalidate(&self, path: &Path) {
+        self.stats.lock().await.remove(path);
+        self.dirs.lock().await.remove(path);
+        if let Some(parent) = path.parent() {
+            self.dirs.lock().await.remove(parent);
+        }
+    }
+
+    /// Drops `path`, its parent directory's listing, and every cached entry
+    /// nested under `path`, called after a directory rename (or any other
+    /// operation that moves a whole subtree) so stale `stat`/`read_dir`
+    /// results for children left over from the old location don't linger
+    /// until TTL expiry.
+    async fn invalidate_subtree(&self, path: &Path) {
+        self.invalidate(path).await;
+        self.stats.lock().await.retain(|p, _| !p.starts_with(path));
+        self.dirs.lock().await.retain(|p, _| !p.starts_with(path));
+    }
+}
+
+fn evict_expired_if_full<V>(map: &mut HashMap<PathBuf, (V, Instant)>, ttl: Duration, capacity: usize) {
+    if map.len() < capacity {
+        return;
+    }
+    map.retain(|_, (_, inserted_at)| inserted_at.elapsed() <= ttl);
+    if map.len() >= capacity {
+        if let Some(oldest) = map
+            .iter()
+            .min_by_key(|(_, (_, inserted_at))| *inserted_at)
+            .map(|(path, _)| path.clone())
+        {
+            map.remove(&oldest);
+        }
+    }
+}
+
 fn build_dir_path(path: &Path) -> String {
     let mut dir_path = path.to_string_lossy().to_string();
     if !dir_path.ends_with('/') {
@@ -259,6 +1067,7 @@ fn opendal_filemode_to_filetype(mode: EntryMode) -> FileType {
 
 #[cfg(test)]
 mod test {
+    use super::*;
     use crate::config::AppConfig;
     use crate::s3_filesystem::extract_s3_config;
     use crate::s3_filesystem::tests::s3_test_config;
@@ -327,4 +1136,584 @@ mod test {
             }
         }
     }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_file() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from = "/s1/fileset1/gvfs_test/rename_src";
+        let to = "/s1/fileset1/gvfs_test/rename_dst";
+        op.write(from, "rename me".as_bytes().to_vec())
+            .await
+            .expect("seed source file");
+        let _ = op.remove(vec![to.to_string()]).await;
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        fs.rename(Path::new(from), Path::new(to))
+            .await
+            .expect("rename file");
+
+        assert!(op.stat(from).await.is_err());
+        assert!(op.stat(to).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_dir() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from_dir = "/s1/fileset1/gvfs_test/rename_dir_src/";
+        let to_dir = "/s1/fileset1/gvfs_test/rename_dir_dst/";
+        op.write(
+            &format!("{from_dir}a.txt"),
+            "a".as_bytes().to_vec(),
+        )
+        .await
+        .expect("seed dir entry");
+        let _ = op.remove_all(to_dir).await;
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        fs.rename(
+            Path::new(from_dir.trim_end_matches('/')),
+            Path::new(to_dir.trim_end_matches('/')),
+        )
+        .await
+        .expect("rename dir");
+
+        assert!(op.stat(&format!("{to_dir}a.txt")).await.is_ok());
+        assert!(op.stat(from_dir).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_empty_dir() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from_dir = "/s1/fileset1/gvfs_test/rename_empty_src/";
+        let to_dir = "/s1/fileset1/gvfs_test/rename_empty_dst/";
+        let _ = op.remove_all(to_dir).await;
+        op.create_dir(from_dir).await.expect("seed empty source dir");
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        fs.rename(
+            Path::new(from_dir.trim_end_matches('/')),
+            Path::new(to_dir.trim_end_matches('/')),
+        )
+        .await
+        .expect("rename empty dir");
+
+        // The destination directory must exist even though nothing was
+        // copied into it, and the source must be gone.
+        assert!(op.stat(to_dir).await.is_ok());
+        assert!(op.stat(from_dir).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_onto_existing_file_overwrites() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from = "/s1/fileset1/gvfs_test/rename_overwrite_src";
+        let to = "/s1/fileset1/gvfs_test/rename_overwrite_dst";
+        op.write(from, "new content".as_bytes().to_vec())
+            .await
+            .expect("seed source file");
+        op.write(to, "stale content".as_bytes().to_vec())
+            .await
+            .expect("seed existing destination file");
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        fs.rename(Path::new(from), Path::new(to))
+            .await
+            .expect("rename onto existing file should overwrite it");
+
+        assert!(op.stat(from).await.is_err());
+        let content = op.read(to).await.expect("read destination").to_vec();
+        assert_eq!(content, "new content".as_bytes());
+    }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_onto_existing_empty_dir_succeeds() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from_dir = "/s1/fileset1/gvfs_test/rename_onto_empty_src/";
+        let to_dir = "/s1/fileset1/gvfs_test/rename_onto_empty_dst/";
+        op.write(&format!("{from_dir}a.txt"), "a".as_bytes().to_vec())
+            .await
+            .expect("seed source dir");
+        op.create_dir(to_dir).await.expect("seed existing empty destination dir");
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        fs.rename(
+            Path::new(from_dir.trim_end_matches('/')),
+            Path::new(to_dir.trim_end_matches('/')),
+        )
+        .await
+        .expect("rename onto an existing empty dir should succeed");
+
+        assert!(op.stat(&format!("{to_dir}a.txt")).await.is_ok());
+        assert!(op.stat(from_dir).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn s3_ut_test_s3_rename_onto_nonempty_dir_fails() {
+        test_enable_with!(RUN_TEST_WITH_S3);
+        let config = s3_test_config();
+        let op = create_opendal(&config);
+
+        let from_dir = "/s1/fileset1/gvfs_test/rename_conflict_src/";
+        let to_dir = "/s1/fileset1/gvfs_test/rename_conflict_dst/";
+        op.write(&format!("{from_dir}a.txt"), "a".as_bytes().to_vec())
+            .await
+            .expect("seed source dir");
+        op.write(&format!("{to_dir}existing.txt"), "b".as_bytes().to_vec())
+            .await
+            .expect("seed non-empty destination dir");
+
+        let fs = OpenDalFileSystem::new(op.clone(), &config, &FileSystemContext::default());
+        let result = fs
+            .rename(
+                Path::new(from_dir.trim_end_matches('/')),
+                Path::new(to_dir.trim_end_matches('/')),
+            )
+            .await;
+
+        assert_eq!(result.unwrap_err(), Errno::from(libc::ENOTEMPTY));
+    }
+
+    #[tokio::test]
+    async fn test_file_writer_spills_large_write_to_temp_file_and_round_trips() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let file_name = "big.bin";
+
+        let cache_dir = std::env::temp_dir().join("gravitino-fuse-test-staging-spill");
+        let mut writer = FileWriterImpl {
+            op: op.clone(),
+            file_name: file_name.to_string(),
+            staging: Arc::new(AsyncMutex::new(WriteStaging::new(0, cache_dir.clone()))),
+            meta_cache: Arc::new(MetaCache::new(Duration::from_secs(60), 128)),
+            active_writers: Arc::new(AsyncMutex::new(HashMap::new())),
+            path: PathBuf::from("/big.bin"),
+            writer_id: next_writer_id(),
+        };
+
+        // Comfortably cross STAGING_MEMORY_LIMIT_BYTES so the scratch has to
+        // spill to a temp file.
+        let size = (STAGING_MEMORY_LIMIT_BYTES + 1024 * 1024) as usize;
+        let content: Vec<u8> = (0..size).map(|i| (i % 251) as u8).collect();
+        writer.write(0, &content).await.expect("large write");
+
+        assert!(
+            matches!(
+                &*writer.staging.lock().await,
+                WriteStaging {
+                    scratch: Scratch::File { .. },
+                    ..
+                }
+            ),
+            "a write past the memory limit should have spilled to a temp file"
+        );
+
+        writer.close().await.expect("close");
+
+        let round_tripped = op.read(file_name).await.expect("read").to_vec();
+        assert_eq!(round_tripped, content);
+    }
+
+    #[tokio::test]
+    async fn test_file_writer_header_rewrite_preserves_trailing_bytes() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let file_name = "header.bin";
+        let original = vec![9u8; 10];
+        op.write(file_name, original.clone())
+            .await
+            .expect("seed file");
+
+        let mut writer = FileWriterImpl {
+            op: op.clone(),
+            file_name: file_name.to_string(),
+            staging: Arc::new(AsyncMutex::new(WriteStaging::new(
+                original.len() as u64,
+                std::env::temp_dir().join("gravitino-fuse-test-staging"),
+            ))),
+            meta_cache: Arc::new(MetaCache::new(Duration::from_secs(60), 128)),
+            active_writers: Arc::new(AsyncMutex::new(HashMap::new())),
+            path: PathBuf::from("/header.bin"),
+            writer_id: next_writer_id(),
+        };
+
+        writer.write(0, &[1, 2, 3]).await.expect("header write");
+        writer.close().await.expect("close");
+
+        let content = op.read(file_name).await.expect("read").to_vec();
+        assert_eq!(content, vec![1, 2, 3, 9, 9, 9, 9, 9, 9, 9]);
+    }
+
+    #[tokio::test]
+    async fn test_file_writer_close_without_writes_preserves_whole_file() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let file_name = "untouched.bin";
+        let original = vec![5u8; 6];
+        op.write(file_name, original.clone())
+            .await
+            .expect("seed file");
+
+        let mut writer = FileWriterImpl {
+            op: op.clone(),
+            file_name: file_name.to_string(),
+            staging: Arc::new(AsyncMutex::new(WriteStaging::new(
+                original.len() as u64,
+                std::env::temp_dir().join("gravitino-fuse-test-staging"),
+            ))),
+            meta_cache: Arc::new(MetaCache::new(Duration::from_secs(60), 128)),
+            active_writers: Arc::new(AsyncMutex::new(HashMap::new())),
+            path: PathBuf::from("/untouched.bin"),
+            writer_id: next_writer_id(),
+        };
+
+        writer.close().await.expect("close with no writes");
+
+        let content = op.read(file_name).await.expect("read").to_vec();
+        assert_eq!(content, original);
+    }
+
+    #[tokio::test]
+    async fn test_set_attr_coordinates_with_active_staged_writer() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let fs = OpenDalFileSystem::new(op.clone(), &AppConfig::default(), &FileSystemContext::default());
+
+        let path = Path::new("/coord.bin");
+        op.write("/coord.bin", vec![9u8; 10])
+            .await
+            .expect("seed file");
+
+        let staging = Arc::new(AsyncMutex::new(WriteStaging::new(
+            10,
+            std::env::temp_dir().join("gravitino-fuse-test-staging"),
+        )));
+        let writer_id = next_writer_id();
+        fs.active_writers
+            .lock()
+            .await
+            .entry(path.to_path_buf())
+            .or_default()
+            .push((writer_id, staging.clone()));
+
+        // A truncate (e.g. from another open fd) arrives while a writer is
+        // already staged on this path.
+        let mut truncated_stat = fs.stat(path).await.expect("stat");
+        truncated_stat.size = 4;
+        fs.set_attr(path, &truncated_stat, false)
+            .await
+            .expect("set_attr");
+
+        // The writer's own close() must honor the truncate rather than
+        // clobbering it with the length it happened to buffer.
+        let mut writer = FileWriterImpl {
+            op: op.clone(),
+            file_name: "coord.bin".to_string(),
+            staging,
+            meta_cache: fs.meta_cache.clone(),
+            active_writers: fs.active_writers.clone(),
+            path: path.to_path_buf(),
+            writer_id,
+        };
+        writer.close().await.expect("close");
+
+        let content = op.read("coord.bin").await.expect("read").to_vec();
+        assert_eq!(content, vec![9u8; 4]);
+    }
+
+    #[tokio::test]
+    async fn test_set_attr_coordinates_with_two_concurrent_staged_writers() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let fs = OpenDalFileSystem::new(op.clone(), &AppConfig::default(), &FileSystemContext::default());
+
+        let path = Path::new("/coord2.bin");
+        op.write("/coord2.bin", vec![9u8; 10])
+            .await
+            .expect("seed file");
+
+        let staging_a = Arc::new(AsyncMutex::new(WriteStaging::new(
+            10,
+            std::env::temp_dir().join("gravitino-fuse-test-staging-a"),
+        )));
+        let staging_b = Arc::new(AsyncMutex::new(WriteStaging::new(
+            10,
+            std::env::temp_dir().join("gravitino-fuse-test-staging-b"),
+        )));
+
+        let writer_id_a = next_writer_id();
+        let writer_id_b = next_writer_id();
+        {
+            let mut active_writers = fs.active_writers.lock().await;
+            active_writers
+                .entry(path.to_path_buf())
+                .or_default()
+                .push((writer_id_a, staging_a.clone()));
+            active_writers
+                .entry(path.to_path_buf())
+                .or_default()
+                .push((writer_id_b, staging_b.clone()));
+        }
+
+        // Two fds have this path open for write. A truncate must reach both
+        // of their staged target lengths, not just whichever opened first.
+        let mut truncated_stat = fs.stat(path).await.expect("stat");
+        truncated_stat.size = 4;
+        fs.set_attr(path, &truncated_stat, false)
+            .await
+            .expect("set_attr");
+
+        assert_eq!(staging_a.lock().await.target_len, 4);
+        assert_eq!(staging_b.lock().await.target_len, 4);
+
+        // Closing writer A must only remove its own entry, leaving writer
+        // B's still live in the map.
+        let mut writer_a = FileWriterImpl {
+            op: op.clone(),
+            file_name: "coord2.bin".to_string(),
+            staging: staging_a,
+            meta_cache: fs.meta_cache.clone(),
+            active_writers: fs.active_writers.clone(),
+            path: path.to_path_buf(),
+            writer_id: writer_id_a,
+        };
+        writer_a.close().await.expect("close writer a");
+
+        let remaining = fs.active_writers.lock().await.get(path).cloned();
+        let remaining = remaining.expect("writer b's entry should still be present");
+        assert_eq!(remaining.len(), 1);
+        assert_eq!(remaining[0].0, writer_id_b);
+    }
+
+    #[tokio::test]
+    async fn test_set_attr_shrinks_object_to_requested_size() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let fs = OpenDalFileSystem::new(op.clone(), &AppConfig::default(), &FileSystemContext::default());
+
+        let path = Path::new("/shrink.bin");
+        op.write("/shrink.bin", vec![9u8; 10]).await.expect("seed file");
+
+        let mut file_stat = fs.stat(path).await.expect("stat");
+        file_stat.size = 4;
+        fs.set_attr(path, &file_stat, false).await.expect("shrink");
+
+        let content = op.read("/shrink.bin").await.expect("read").to_vec();
+        assert_eq!(content, vec![9u8; 4]);
+    }
+
+    #[tokio::test]
+    async fn test_set_attr_grows_object_zero_filled() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let fs = OpenDalFileSystem::new(op.clone(), &AppConfig::default(), &FileSystemContext::default());
+
+        let path = Path::new("/grow.bin");
+        op.write("/grow.bin", vec![9u8; 4]).await.expect("seed file");
+
+        let mut file_stat = fs.stat(path).await.expect("stat");
+        file_stat.size = 8;
+        fs.set_attr(path, &file_stat, false).await.expect("grow");
+
+        let content = op.read("/grow.bin").await.expect("read").to_vec();
+        assert_eq!(content, vec![9, 9, 9, 9, 0, 0, 0, 0]);
+    }
+
+    #[tokio::test]
+    async fn test_readahead_fills_window_during_sequential_reads() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+
+        let file_name = "bench.bin";
+        let content = vec![7u8; 4 * 1024 * 1024];
+        op.write(file_name, content.clone())
+            .await
+            .expect("seed file");
+
+        let mut reader = FileReaderImpl {
+            op: op.clone(),
+            file_name: file_name.to_string(),
+            last_served_end: 0,
+            window: Arc::new(AsyncMutex::new(None)),
+            max_window_bytes: 4 * 1024 * 1024,
+            pending_prefetch: None,
+        };
+
+        let chunk = 256 * 1024usize;
+        let mut offset = 0u64;
+        while (offset as usize) < content.len() {
+            let data = reader
+                .read(offset, chunk as u32)
+                .await
+                .expect("sequential read");
+            assert_eq!(data.len(), chunk);
+            offset += chunk as u64;
+        }
+
+        // Let the most recently kicked-off background prefetch land.
+        if let Some(handle) = reader.pending_prefetch.take() {
+            handle.await.expect("prefetch task");
+        }
+
+        // The sequential scan should have left a prefetched window behind.
+        assert!(reader.window.lock().await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_readahead_resets_on_random_seek() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+
+        let file_name = "seek.bin";
+        op.write(file_name, vec![1u8; 1024 * 1024])
+            .await
+            .expect("seed file");
+
+        let mut reader = FileReaderImpl {
+            op: op.clone(),
+            file_name: file_name.to_string(),
+            last_served_end: 0,
+            window: Arc::new(AsyncMutex::new(None)),
+            max_window_bytes: 512 * 1024,
+            pending_prefetch: None,
+        };
+
+        reader.read(0, 4096).await.expect("first read");
+        if let Some(handle) = reader.pending_prefetch.take() {
+            handle.await.expect("prefetch task");
+        }
+        assert!(reader.window.lock().await.is_some());
+
+        // A backward seek should drop the stale window instead of serving
+        // garbage from it.
+        reader.read(0, 4096).await.expect("random seek read");
+        assert!(reader.window.lock().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_meta_cache_hit_serves_from_memory() {
+        let cache = MetaCache::new(Duration::from_secs(60), 128);
+        let path = PathBuf::from("/a/b.txt");
+        assert!(cache.get_stat(&path).await.is_none());
+
+        let mut stat = FileStat::new_file_filestat_with_path(&path, 0);
+        stat.size = 42;
+        cache.put_stat(&path, stat).await;
+
+        // Second lookup is served from the cache, not the backend.
+        let cached = cache.get_stat(&path).await.unwrap().unwrap();
+        assert_eq!(cached.size, 42);
+    }
+
+    #[tokio::test]
+    async fn test_meta_cache_invalidate_clears_entry_and_parent_dir() {
+        let cache = MetaCache::new(Duration::from_secs(60), 128);
+        let path = PathBuf::from("/a/b.txt");
+        let parent = PathBuf::from("/a");
+
+        cache
+            .put_stat(&path, FileStat::new_file_filestat_with_path(&path, 0))
+            .await;
+        cache.put_dir(&parent, vec![]).await;
+
+        cache.invalidate(&path).await;
+
+        assert!(cache.get_stat(&path).await.is_none());
+        assert!(cache.get_dir(&parent).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_meta_cache_negative_lookup_is_cached() {
+        let cache = MetaCache::new(Duration::from_secs(60), 128);
+        let path = PathBuf::from("/missing");
+
+        cache.put_not_found(&path).await;
+        assert!(cache.get_stat(&path).await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_meta_cache_expires_after_ttl() {
+        let cache = MetaCache::new(Duration::from_millis(10), 128);
+        let path = PathBuf::from("/a/b.txt");
+        cache
+            .put_stat(&path, FileStat::new_file_filestat_with_path(&path, 0))
+            .await;
+
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        assert!(cache.get_stat(&path).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_meta_cache_invalidate_subtree_clears_nested_entries() {
+        let cache = MetaCache::new(Duration::from_secs(60), 128);
+        let dir = PathBuf::from("/a");
+        let child = PathBuf::from("/a/b.txt");
+        let sibling = PathBuf::from("/ab/c.txt");
+
+        cache
+            .put_stat(&child, FileStat::new_file_filestat_with_path(&child, 0))
+            .await;
+        cache
+            .put_stat(&sibling, FileStat::new_file_filestat_with_path(&sibling, 0))
+            .await;
+        cache.put_dir(&dir, vec![]).await;
+
+        cache.invalidate_subtree(&dir).await;
+
+        assert!(cache.get_stat(&child).await.is_none());
+        assert!(cache.get_dir(&dir).await.is_none());
+        // A differently-prefixed path that merely shares a string prefix
+        // with the renamed dir must survive.
+        assert!(cache.get_stat(&sibling).await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_stat_second_call_is_served_from_cache_not_backend() {
+        let builder = services::Memory::default();
+        let op = Operator::new(builder).expect("memory operator").finish();
+        let fs = OpenDalFileSystem::new(op.clone(), &AppConfig::default(), &FileSystemContext::default());
+
+        let path = Path::new("/cached.bin");
+        op.write("/cached.bin", vec![1u8; 4]).await.expect("seed file");
+
+        let first = fs.stat(path).await.expect("first stat");
+        assert_eq!(first.size, 4);
+
+        // Mutate the backend object directly, bypassing the filesystem (and
+        // so never invalidating the cache). If `stat` were hitting the
+        // backend again, this would be reflected in the second call.
+        op.write("/cached.bin", vec![1u8; 9]).await.expect("mutate backend");
+
+        let second = fs.stat(path).await.expect("second stat");
+        assert_eq!(
+            second.size, first.size,
+            "second stat should be served from the cache, not the mutated backend object"
+        );
+
+        // The cache can still be proven stale directly against the backend.
+        let backend_meta = op.stat("/cached.bin").await.expect("backend stat");
+        assert_eq!(backend_meta.content_length(), 9);
+    }
+
+    #[cfg(feature = "storage-memory")]
+    #[test]
+    fn test_from_config_builds_filesystem_via_operator_registry() {
+        let config = AppConfig::default_with_storage_uri("memory://");
+        OpenDalFileSystem::from_config(&config, &FileSystemContext::default())
+            .expect("from_config should build via the operator registry");
+    }
 }
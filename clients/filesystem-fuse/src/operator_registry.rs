@@ -0,0 +1,153 @@
+/*
+ * Licensed to the Apache Software Foundation (ASF) under one
+ * or more contributor license agreements.  See the NOTICE file
+ * distributed with this work for additional information
+ * regarding copyright ownership.  The ASF licenses this file
+ * to you under the Apache License, Version 2.0 (the
+ * "License"); you may not use this file except in compliance
+ * with the License.  You may obtain a copy of the License at
+ *
+ *  http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing,
+ * software distributed under the License is distributed on an
+ * "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+ * KIND, either express or implied.  See the License for the
+ * specific language governing permissions and limitations
+ * under the License.
+ */
+use crate::config::AppConfig;
+use crate::filesystem::Result;
+use fuse3::Errno;
+use log::error;
+use opendal::layers::{LoggingLayer, RetryLayer};
+use opendal::{Builder, Operator};
+
+/// Builds a fully layered [`Operator`] for whichever backend `config`'s
+/// storage URI scheme selects (`s3://`, `gs://`, `abfss://`, `fs://`,
+/// `memory://`), so `OpenDalFileSystem::new` can be handed an `Operator`
+/// without caring which backend is behind it. Backends are compiled in
+/// behind their own Cargo feature so unused `opendal::services` don't
+/// bloat builds that only need one.
+pub(crate) fn build_operator(config: &AppConfig) -> Result<Operator> {
+    let uri = config.storage_uri();
+    let scheme = uri.split("://").next().unwrap_or("");
+
+    let op = match scheme {
+        "s3" => build_s3(config)?,
+        "gs" => build_gcs(config)?,
+        "abfss" => build_azblob(config)?,
+        "fs" => build_fs(config)?,
+        "memory" => build_memory()?,
+        other => {
+            error!("unsupported storage uri scheme: {other}");
+            return Err(Errno::from(libc::EINVAL));
+        }
+    };
+
+    Ok(op.layer(LoggingLayer::default()).layer(RetryLayer::default()))
+}
+
+fn operator_build_error(err: opendal::Error) -> Errno {
+    error!("failed to build opendal operator: {:?}", err);
+    Errno::from(libc::EINVAL)
+}
+
+#[cfg(feature = "storage-s3")]
+fn build_s3(config: &AppConfig) -> Result<Operator> {
+    let opendal_config = crate::s3_filesystem::extract_s3_config(config);
+    let builder = opendal::services::S3::from_map(opendal_config);
+    Operator::new(builder)
+        .map_err(operator_build_error)
+        .map(|b| b.finish())
+}
+
+#[cfg(not(feature = "storage-s3"))]
+fn build_s3(_config: &AppConfig) -> Result<Operator> {
+    error!("gravitino-fuse was built without the storage-s3 feature");
+    Err(Errno::from(libc::EOPNOTSUPP))
+}
+
+#[cfg(feature = "storage-gcs")]
+fn build_gcs(config: &AppConfig) -> Result<Operator> {
+    let builder = opendal::services::Gcs::default()
+        .bucket(&config.gcs_bucket())
+        .root(&config.storage_root());
+    Operator::new(builder)
+        .map_err(operator_build_error)
+        .map(|b| b.finish())
+}
+
+#[cfg(not(feature = "storage-gcs"))]
+fn build_gcs(_config: &AppConfig) -> Result<Operator> {
+    error!("gravitino-fuse was built without the storage-gcs feature");
+    Err(Errno::from(libc::EOPNOTSUPP))
+}
+
+#[cfg(feature = "storage-azblob")]
+fn build_azblob(config: &AppConfig) -> Result<Operator> {
+    let builder = opendal::services::Azblob::default()
+        .container(&config.azblob_container())
+        .root(&config.storage_root());
+    Operator::new(builder)
+        .map_err(operator_build_error)
+        .map(|b| b.finish())
+}
+
+#[cfg(not(feature = "storage-azblob"))]
+fn build_azblob(_config: &AppConfig) -> Result<Operator> {
+    error!("gravitino-fuse was built without the storage-azblob feature");
+    Err(Errno::from(libc::EOPNOTSUPP))
+}
+
+#[cfg(feature = "storage-fs")]
+fn build_fs(config: &AppConfig) -> Result<Operator> {
+    let builder = opendal::services::Fs::default().root(&config.storage_root());
+    Operator::new(builder)
+        .map_err(operator_build_error)
+        .map(|b| b.finish())
+}
+
+#[cfg(not(feature = "storage-fs"))]
+fn build_fs(_config: &AppConfig) -> Result<Operator> {
+    error!("gravitino-fuse was built without the storage-fs feature");
+    Err(Errno::from(libc::EOPNOTSUPP))
+}
+
+#[cfg(feature = "storage-memory")]
+fn build_memory() -> Result<Operator> {
+    let builder = opendal::services::Memory::default();
+    Operator::new(builder)
+        .map_err(operator_build_error)
+        .map(|b| b.finish())
+}
+
+#[cfg(not(feature = "storage-memory"))]
+fn build_memory() -> Result<Operator> {
+    error!("gravitino-fuse was built without the storage-memory feature");
+    Err(Errno::from(libc::EOPNOTSUPP))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::config::AppConfig;
+
+    #[cfg(feature = "storage-memory")]
+    #[tokio::test]
+    async fn test_build_operator_memory() {
+        let config = AppConfig::default_with_storage_uri("memory://");
+        let op = build_operator(&config).expect("memory operator should build");
+        op.write("a.txt", "hi".as_bytes().to_vec())
+            .await
+            .expect("write to memory backend");
+        let content = op.read("a.txt").await.expect("read from memory backend");
+        assert_eq!(content.to_bytes().as_ref(), b"hi");
+    }
+
+    #[test]
+    fn test_build_operator_rejects_unknown_scheme() {
+        let config = AppConfig::default_with_storage_uri("ftp://host/path");
+        assert!(build_operator(&config).is_err());
+    }
+}